@@ -1,25 +1,33 @@
-use super::{Request, RequestBuilderPerform};
+use super::{Outcome, Request, RequestBuilderPerform, TargetProcess};
 use crate::settings::rewrite::Rewrite;
-use crate::settings::targets::TargetProcess;
 use anyhow::Context;
 use autopulse_database::models::ScanEvent;
 use autopulse_utils::get_url;
-use futures::future::join_all;
+use futures::{future::join_all, TryStreamExt};
 use reqwest::header;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display, io::Cursor, path::Path};
+use std::{collections::HashMap, fmt::Display, io::Read, path::Path, sync::Arc};
 use struson::{
     json_path,
     reader::{JsonReader, JsonStreamReader},
 };
-use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::{mpsc::UnboundedReceiver, Semaphore};
+use tokio_util::io::{StreamReader, SyncIoBridge};
 use tracing::{debug, error, info, warn};
 
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
 #[doc(hidden)]
 const fn default_true() -> bool {
     true
 }
 
+#[doc(hidden)]
+const fn default_concurrency() -> usize {
+    8
+}
+
 #[derive(Serialize, Clone, Deserialize)]
 pub struct Emby {
     /// URL to the Jellyfin/Emby server
@@ -37,6 +45,13 @@ pub struct Emby {
     /// HTTP request options
     #[serde(default)]
     pub request: Request,
+    /// Maximum number of concurrent scan/refresh requests in flight (default: 8)
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Durable queue for transient failures that outlive this process (wired up by the
+    /// scheduler at startup; not part of the on-disk config)
+    #[serde(skip)]
+    pub retry_queue: Option<Arc<dyn retry_queue::RetryQueue>>,
 }
 
 /// Metadata refresh mode for Jellyfin/Emby
@@ -125,7 +140,331 @@ struct ScanPathsResponse {
     results: Vec<ScanPathResponse>,
 }
 
+/// Prometheus metrics for the scan pipeline, recorded against the global recorder
+/// installed by [`scan_metrics::init_metrics`]. Gated behind the `metrics` feature so
+/// builds that don't want the dependency pay nothing for it. [`scan_metrics::init_metrics`]
+/// is called once by the scheduler at startup, next to [`Emby::with_retry_queue`].
+///
+/// Requires this crate's `Cargo.toml` to declare a `metrics` feature enabling the
+/// `metrics` and `metrics-exporter-prometheus` optional dependencies (not present in
+/// this tree's manifest).
+#[cfg(feature = "metrics")]
+pub mod scan_metrics {
+    /// Installs a Prometheus recorder as the global `metrics` recorder and starts its
+    /// HTTP exporter, the way `pict-rs` wires up a `PrometheusBuilder` at startup.
+    /// Must be called once, before any `Emby` target processes events, or every
+    /// `counter!`/`histogram!` call in this module is a no-op.
+    pub fn init_metrics(listen_addr: std::net::SocketAddr) -> anyhow::Result<()> {
+        metrics_exporter_prometheus::PrometheusBuilder::new()
+            .with_http_listener(listen_addr)
+            .install()?;
+
+        Ok(())
+    }
+
+    pub const TIER_BATCH: &str = "batch";
+    pub const TIER_INDIVIDUAL: &str = "individual";
+    pub const TIER_ENUMERATION: &str = "enumeration";
+
+    pub fn scan_attempted(url: &str, library: &str, tier: &str) {
+        metrics::counter!(
+            "autopulse_emby_scans_attempted_total",
+            "url" => url.to_string(), "library" => library.to_string(), "tier" => tier.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn scan_succeeded(url: &str, library: &str, tier: &str) {
+        metrics::counter!(
+            "autopulse_emby_scans_succeeded_total",
+            "url" => url.to_string(), "library" => library.to_string(), "tier" => tier.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn scan_skipped_missing(url: &str, library: &str, tier: &str) {
+        metrics::counter!(
+            "autopulse_emby_scans_skipped_missing_total",
+            "url" => url.to_string(), "library" => library.to_string(), "tier" => tier.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn backoff_retry(url: &str, count: usize) {
+        metrics::counter!("autopulse_emby_backoff_retries_total", "url" => url.to_string())
+            .increment(count as u64);
+    }
+
+    pub fn scan_latency(url: &str, library: &str, elapsed: std::time::Duration) {
+        metrics::histogram!(
+            "autopulse_emby_scan_request_duration_seconds",
+            "url" => url.to_string(), "library" => library.to_string()
+        )
+        .record(elapsed.as_secs_f64());
+    }
+
+    pub fn process_duration(url: &str, elapsed: std::time::Duration) {
+        metrics::histogram!("autopulse_emby_process_duration_seconds", "url" => url.to_string())
+            .record(elapsed.as_secs_f64());
+    }
+}
+
+/// Durable retry queue for targeted scans that end in a transient [`Outcome::Failure`].
+///
+/// The in-process backoff in [`Emby::process`] only covers ~50s; if the host restarts
+/// or the server stays down longer than that, events would otherwise be abandoned.
+/// Persisting them (backed by `autopulse_database`) lets a background worker keep
+/// retrying across restarts until the item succeeds or `max_attempts` is reached.
+pub mod retry_queue {
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    use super::{Emby, Item};
+
+    /// A single queued retry: the event, how many attempts have been made, and
+    /// when the next attempt is eligible to run.
+    #[derive(Debug, Clone)]
+    pub struct RetryJob {
+        pub event_id: String,
+        pub path: String,
+        /// Item id this path already resolved to, if Tier 3 found it before the
+        /// refresh itself failed. `None` means the targeted-scan plugin never
+        /// resolved the path, so the worker has nothing to refresh directly.
+        pub item_id: Option<String>,
+        pub attempt: u32,
+        pub next_attempt_at: i64,
+        pub last_error: String,
+    }
+
+    /// Persists and hands back retry jobs. Implemented against `autopulse_database`
+    /// so enqueued jobs survive process restarts.
+    #[async_trait]
+    pub trait RetryQueue: Send + Sync {
+        /// Enqueue (or bump the attempt count of) a job for `event_id`. `item_id`
+        /// is `Some` when the path was already resolved to an item (e.g. Tier 3
+        /// found it but the refresh itself failed), letting the worker fall back to
+        /// refreshing that item when the re-run targeted scan still fails.
+        async fn enqueue(
+            &self,
+            event_id: &str,
+            path: &str,
+            item_id: Option<&str>,
+            reason: &str,
+        ) -> anyhow::Result<()>;
+
+        /// Fetch jobs whose `next_attempt_at` has elapsed, for the worker to retry.
+        async fn due_jobs(&self) -> anyhow::Result<Vec<RetryJob>>;
+
+        /// Mark a job as resolved (succeeded, or abandoned past `max_attempts`).
+        async fn remove(&self, event_id: &str) -> anyhow::Result<()>;
+
+        /// Record a failed retry attempt, bumping the attempt count and backing
+        /// off the next eligible time.
+        async fn record_attempt_failed(&self, event_id: &str, reason: &str) -> anyhow::Result<()>;
+    }
+
+    /// Background worker that drains the queue, re-invoking the targeted scan (falling
+    /// back to a metadata refresh) for each due job until it succeeds or `max_attempts`
+    /// is reached.
+    pub async fn run_worker(
+        emby: Arc<Emby>,
+        queue: Arc<dyn RetryQueue>,
+        max_attempts: u32,
+        poll_interval: std::time::Duration,
+    ) {
+        loop {
+            match queue.due_jobs().await {
+                Ok(jobs) => {
+                    for job in jobs {
+                        let scan_result = emby.targeted_scan(&job.path).await;
+
+                        let recovered = matches!(
+                            &scan_result,
+                            Ok(r) if matches!(
+                                r.status.as_str(),
+                                "Created" | "Refreshed" | "Discovered" | "PathNotFound" | "ParentNotFound"
+                            )
+                        );
+
+                        if recovered {
+                            tracing::info!("retry queue: scan succeeded for {}", job.path);
+                            if let Err(e) = queue.remove(&job.event_id).await {
+                                tracing::error!("failed to remove completed retry job: {}", e);
+                            }
+                            continue;
+                        }
+
+                        // The targeted-scan plugin still can't resolve this path (the
+                        // case Tier 3 hands us), so fall back to a metadata refresh if
+                        // we already know which item it resolved to.
+                        let refresh_result = match &job.item_id {
+                            Some(item_id) => Some(
+                                emby.refresh_item(&Item {
+                                    id: item_id.clone(),
+                                    path: Some(job.path.clone()),
+                                })
+                                .await,
+                            ),
+                            None => None,
+                        };
+
+                        let last_error = match (&scan_result, &refresh_result) {
+                            (_, Some(Ok(()))) => {
+                                tracing::info!("retry queue: refresh succeeded for {}", job.path);
+                                if let Err(e) = queue.remove(&job.event_id).await {
+                                    tracing::error!("failed to remove completed retry job: {}", e);
+                                }
+                                continue;
+                            }
+                            (_, Some(Err(e))) => e.to_string(),
+                            (Err(e), None) => e.to_string(),
+                            (Ok(r), None) => r.status.clone(),
+                        };
+
+                        if job.attempt + 1 >= max_attempts {
+                            tracing::error!(
+                                "retry queue: giving up on {} after {} attempts",
+                                job.path, job.attempt + 1
+                            );
+                            if let Err(e) = queue.remove(&job.event_id).await {
+                                tracing::error!("failed to remove abandoned retry job: {}", e);
+                            }
+                        } else if let Err(e) =
+                            queue.record_attempt_failed(&job.event_id, &last_error).await
+                        {
+                            tracing::error!("failed to record retry attempt: {}", e);
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("failed to fetch due retry jobs: {}", e),
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// [`RetryQueue`] backed by the `autopulse_database` connection pool, so queued
+    /// jobs are durable across restarts.
+    ///
+    /// Requires a `scan_retry_jobs` table and matching `ScanRetryJob` model in the
+    /// `autopulse_database` crate (out of this crate's tree), with columns mirroring
+    /// [`RetryJob`] (`event_id` unique, `path`, `item_id` nullable, `attempt`,
+    /// `next_attempt_at`, `last_error`) and associated functions `upsert`, `due`,
+    /// `remove`, and `record_attempt_failed` with the signatures used below.
+    pub struct DatabaseRetryQueue {
+        pool: autopulse_database::DbPool,
+    }
+
+    impl DatabaseRetryQueue {
+        pub fn new(pool: autopulse_database::DbPool) -> Self {
+            Self { pool }
+        }
+    }
+
+    #[async_trait]
+    impl RetryQueue for DatabaseRetryQueue {
+        async fn enqueue(
+            &self,
+            event_id: &str,
+            path: &str,
+            item_id: Option<&str>,
+            reason: &str,
+        ) -> anyhow::Result<()> {
+            autopulse_database::models::ScanRetryJob::upsert(
+                &self.pool,
+                event_id,
+                path,
+                item_id,
+                reason,
+            )
+            .await
+        }
+
+        async fn due_jobs(&self) -> anyhow::Result<Vec<RetryJob>> {
+            let rows = autopulse_database::models::ScanRetryJob::due(&self.pool).await?;
+
+            Ok(rows
+                .into_iter()
+                .map(|row| RetryJob {
+                    event_id: row.event_id,
+                    path: row.path,
+                    item_id: row.item_id,
+                    attempt: row.attempt as u32,
+                    next_attempt_at: row.next_attempt_at,
+                    last_error: row.last_error,
+                })
+                .collect())
+        }
+
+        async fn remove(&self, event_id: &str) -> anyhow::Result<()> {
+            autopulse_database::models::ScanRetryJob::remove(&self.pool, event_id).await
+        }
+
+        async fn record_attempt_failed(&self, event_id: &str, reason: &str) -> anyhow::Result<()> {
+            autopulse_database::models::ScanRetryJob::record_attempt_failed(
+                &self.pool, event_id, reason,
+            )
+            .await
+        }
+    }
+}
+
+/// Records a non-success outcome for `id`, unless a [`Outcome::Success`] was already
+/// recorded for it. An event can match more than one library, so the same id may be
+/// processed more than once (e.g. found in one library, not found in another); a later
+/// miss must not clobber an earlier success.
+fn record_unless_success(outcomes: &mut HashMap<String, Outcome>, id: String, outcome: Outcome) {
+    match outcomes.get(&id) {
+        Some(Outcome::Success { .. }) => {}
+        _ => {
+            outcomes.insert(id, outcome);
+        }
+    }
+}
+
+/// Records a [`Outcome::Success`] for `id`, overwriting whatever is already recorded.
+/// An event can match more than one library, so the same id may be processed more than
+/// once in a nondeterministic order (e.g. not found in one library, then found and
+/// refreshed in another); a later success must always win over an earlier miss.
+fn record_success(outcomes: &mut HashMap<String, Outcome>, id: String, outcome: Outcome) {
+    outcomes.insert(id, outcome);
+}
+
+/// Bridges a [`reqwest::Response`] body into a blocking [`Read`] by feeding its
+/// `bytes_stream` through a [`StreamReader`]/[`SyncIoBridge`], so `struson` can
+/// incrementally parse items as they arrive instead of buffering the whole page.
+fn sync_body_reader(res: reqwest::Response) -> impl Read {
+    let stream = res.bytes_stream().map_err(std::io::Error::other);
+
+    SyncIoBridge::new(StreamReader::new(stream))
+}
+
 impl Emby {
+    /// Wires a [`retry_queue::DatabaseRetryQueue`] backed by `pool` into this target
+    /// and spawns [`retry_queue::run_worker`] to drain it in the background. Called by
+    /// the scheduler at startup for every configured [`Emby`] target, alongside a single
+    /// call to [`scan_metrics::init_metrics`]; without this, `retry_queue` stays `None`
+    /// and transient failures are dropped instead of queued.
+    pub fn with_retry_queue(
+        mut self,
+        pool: autopulse_database::DbPool,
+        max_attempts: u32,
+        poll_interval: std::time::Duration,
+    ) -> Arc<Self> {
+        let queue: Arc<dyn retry_queue::RetryQueue> =
+            Arc::new(retry_queue::DatabaseRetryQueue::new(pool));
+        self.retry_queue = Some(queue.clone());
+
+        let emby = Arc::new(self);
+        tokio::spawn(retry_queue::run_worker(
+            emby.clone(),
+            queue,
+            max_attempts,
+            poll_interval,
+        ));
+        emby
+    }
+
     fn get_client(&self) -> anyhow::Result<reqwest::Client> {
         let mut headers = header::HeaderMap::new();
 
@@ -151,6 +490,15 @@ impl Emby {
         Ok(res.json().await?)
     }
 
+    /// Name of the first library matching `path`, used only to label metrics.
+    #[cfg(feature = "metrics")]
+    fn resolved_library_name(&self, libraries: &[Library], path: &str) -> String {
+        self.get_libraries(libraries, path)
+            .first()
+            .map(|l| l.name.clone())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
     fn get_libraries(&self, libraries: &[Library], path: &str) -> Vec<Library> {
         let ev_path = Path::new(path);
         let mut matched: Vec<Library> = vec![];
@@ -193,24 +541,26 @@ impl Emby {
             .append_pair("EnableTotalRecordCount", "false");
 
         let res = client.get(url).perform().await?;
+        let reader = sync_body_reader(res);
+        let path = path.to_owned();
 
-        // Possibly unneeded unless we can use streams
-        let bytes = res.bytes().await?;
-
-        let mut json_reader = JsonStreamReader::new(Cursor::new(bytes));
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Option<Item>> {
+            let mut json_reader = JsonStreamReader::new(reader);
 
-        json_reader.seek_to(&json_path!["Items"])?;
-        json_reader.begin_array()?;
+            json_reader.seek_to(&json_path!["Items"])?;
+            json_reader.begin_array()?;
 
-        while json_reader.has_next()? {
-            let item: Item = json_reader.deserialize_next()?;
+            while json_reader.has_next()? {
+                let item: Item = json_reader.deserialize_next()?;
 
-            if item.path == Some(path.to_owned()) {
-                return Ok(Some(item));
+                if item.path == Some(path.clone()) {
+                    return Ok(Some(item));
+                }
             }
-        }
 
-        Ok(None)
+            Ok(None)
+        })
+        .await?
     }
 
     fn fetch_items(
@@ -258,23 +608,28 @@ impl Emby {
                     .append_pair("StartIndex", &(page * limit).to_string());
 
                 let res = client.get(page_url).perform().await?;
+                let reader = sync_body_reader(res);
+                let page_tx = tx.clone();
 
-                let bytes = res.bytes().await?;
+                let found_items_count = tokio::task::spawn_blocking(move || -> anyhow::Result<usize> {
+                    let mut json_reader = JsonStreamReader::new(reader);
 
-                let mut json_reader = JsonStreamReader::new(Cursor::new(bytes));
+                    json_reader.seek_to(&json_path!["Items"])?;
+                    json_reader.begin_array()?;
 
-                json_reader.seek_to(&json_path!["Items"])?;
-                json_reader.begin_array()?;
+                    let mut found_items_count = 0;
 
-                let mut found_items_count = 0;
+                    while json_reader.has_next()? {
+                        let item: Item = json_reader.deserialize_next()?;
 
-                while json_reader.has_next()? {
-                    let item: Item = json_reader.deserialize_next()?;
+                        page_tx.send(item)?;
 
-                    tx.send(item)?;
+                        found_items_count += 1;
+                    }
 
-                    found_items_count += 1;
-                }
+                    Ok(found_items_count)
+                })
+                .await??;
 
                 if found_items_count < limit {
                     break;
@@ -408,13 +763,16 @@ impl Emby {
 }
 
 impl TargetProcess for Emby {
-    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<String>> {
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<(String, Outcome)>> {
+        #[cfg(feature = "metrics")]
+        let process_start = Instant::now();
+
         let libraries = self
             .libraries()
             .await
             .context("failed to fetch libraries")?;
 
-        let mut succeeded: HashMap<String, bool> = HashMap::new();
+        let mut outcomes: HashMap<String, Outcome> = HashMap::new();
 
         // Map all events to their rewritten paths, validating each matches a library
         let mut all_with_paths: Vec<(&ScanEvent, String)> = Vec::new();
@@ -423,14 +781,20 @@ impl TargetProcess for Emby {
             let matched_libraries = self.get_libraries(&libraries, &ev_path);
             if matched_libraries.is_empty() {
                 debug!("no matching library for {}, skipping (not a failure)", ev_path);
-                succeeded.insert(ev.id.clone(), true);
+                outcomes.insert(
+                    ev.id.clone(),
+                    Outcome::Success {
+                        item_id: None,
+                        status: "NoMatchingLibrary".to_string(),
+                    },
+                );
                 continue;
             }
             all_with_paths.push((*ev, ev_path));
         }
 
         if all_with_paths.is_empty() {
-            return Ok(vec![]);
+            return Ok(outcomes.into_iter().collect());
         }
 
         // Tier 1: Batch targeted scan for ALL items (plugin handles both new and existing)
@@ -449,20 +813,35 @@ impl TargetProcess for Emby {
                     .collect();
 
                 for (ev, ev_path) in &all_with_paths {
+                    #[cfg(feature = "metrics")]
+                    let library_name = self.resolved_library_name(&libraries, ev_path);
+                    #[cfg(feature = "metrics")]
+                    scan_metrics::scan_attempted(&self.url, &library_name, scan_metrics::TIER_BATCH);
+
                     match result_map.get(ev_path.as_str()) {
                         Some(r) if r.status == "Created" || r.status == "Refreshed" || r.status == "Discovered" => {
                             info!(
                                 "targeted scan succeeded for {}: {} ({})",
                                 ev_path, r.item_id, r.status
                             );
-                            *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                            #[cfg(feature = "metrics")]
+                            scan_metrics::scan_succeeded(&self.url, &library_name, scan_metrics::TIER_BATCH);
+                            outcomes.entry(ev.id.clone()).or_insert_with(|| Outcome::Success {
+                                item_id: Some(r.item_id.clone()),
+                                status: r.status.clone(),
+                            });
                         }
                         Some(r) if r.status == "PathNotFound" || r.status == "ParentNotFound" => {
                             debug!(
                                 "path no longer exists for {} ({}), skipping",
                                 ev_path, r.status
                             );
-                            *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                            #[cfg(feature = "metrics")]
+                            scan_metrics::scan_skipped_missing(&self.url, &library_name, scan_metrics::TIER_BATCH);
+                            outcomes.entry(ev.id.clone()).or_insert_with(|| Outcome::Success {
+                                item_id: None,
+                                status: r.status.clone(),
+                            });
                         }
                         _ => {
                             remaining.push((*ev, ev_path.clone()));
@@ -477,6 +856,7 @@ impl TargetProcess for Emby {
         }
 
         // Tier 2: Individual targeted scans with exponential backoff
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
         let backoff_delays = [5, 15, 30];
         for attempt in 0..=backoff_delays.len() {
             if remaining.is_empty() {
@@ -489,13 +869,28 @@ impl TargetProcess for Emby {
                     "retrying {} targeted scans in {}s (attempt {}/{})",
                     remaining.len(), delay, attempt, backoff_delays.len()
                 );
+                #[cfg(feature = "metrics")]
+                scan_metrics::backoff_retry(&self.url, remaining.len());
                 tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
             }
 
+            #[cfg(feature = "metrics")]
+            let libraries_ref = &libraries;
+
             let scan_futures: Vec<_> = remaining.iter().map(|(ev, ev_path)| {
                 let path = ev_path.clone();
+                let semaphore = semaphore.clone();
                 async move {
+                    let _permit = semaphore.acquire().await.expect("semaphore closed");
+                    #[cfg(feature = "metrics")]
+                    let scan_start = Instant::now();
                     let result = self.targeted_scan(&path).await;
+                    #[cfg(feature = "metrics")]
+                    {
+                        let library_name = self.resolved_library_name(libraries_ref, &path);
+                        scan_metrics::scan_attempted(&self.url, &library_name, scan_metrics::TIER_INDIVIDUAL);
+                        scan_metrics::scan_latency(&self.url, &library_name, scan_start.elapsed());
+                    }
                     (*ev, path, result)
                 }
             }).collect();
@@ -503,6 +898,9 @@ impl TargetProcess for Emby {
 
             let mut still_remaining = Vec::new();
             for (ev, ev_path, result) in results {
+                #[cfg(feature = "metrics")]
+                let library_name = self.resolved_library_name(&libraries, &ev_path);
+
                 match result {
                     Ok(scan_result)
                         if scan_result.status == "PathNotFound"
@@ -512,14 +910,32 @@ impl TargetProcess for Emby {
                             "path no longer exists for {} ({}), skipping",
                             ev_path, scan_result.status
                         );
-                        *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                        #[cfg(feature = "metrics")]
+                        scan_metrics::scan_skipped_missing(&self.url, &library_name, scan_metrics::TIER_INDIVIDUAL);
+                        record_success(
+                            &mut outcomes,
+                            ev.id.clone(),
+                            Outcome::Success {
+                                item_id: None,
+                                status: scan_result.status.clone(),
+                            },
+                        );
                     }
                     Ok(scan_result) => {
                         info!(
                             "targeted scan succeeded for {}: {} ({})",
                             ev_path, scan_result.item_id, scan_result.status
                         );
-                        *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                        #[cfg(feature = "metrics")]
+                        scan_metrics::scan_succeeded(&self.url, &library_name, scan_metrics::TIER_INDIVIDUAL);
+                        record_success(
+                            &mut outcomes,
+                            ev.id.clone(),
+                            Outcome::Success {
+                                item_id: Some(scan_result.item_id.clone()),
+                                status: scan_result.status.clone(),
+                            },
+                        );
                     }
                     Err(e) => {
                         warn!(
@@ -559,15 +975,53 @@ impl TargetProcess for Emby {
                         )
                     })?;
 
-                for (ev, item) in found_in_library {
-                    match self.refresh_item(&item).await {
+                let refresh_futures: Vec<_> = found_in_library.into_iter().map(|(ev, item)| {
+                    let semaphore = semaphore.clone();
+                    async move {
+                        let _permit = semaphore.acquire().await.expect("semaphore closed");
+                        let result = self.refresh_item(&item).await;
+                        (ev, item, result)
+                    }
+                }).collect();
+
+                for (ev, item, result) in join_all(refresh_futures).await {
+                    #[cfg(feature = "metrics")]
+                    scan_metrics::scan_attempted(&self.url, &library.name, scan_metrics::TIER_ENUMERATION);
+
+                    match result {
                         Ok(()) => {
                             debug!("refreshed item: {}", item.id);
-                            *succeeded.entry(ev.id.clone()).or_insert(true) &= true;
+                            #[cfg(feature = "metrics")]
+                            scan_metrics::scan_succeeded(&self.url, &library.name, scan_metrics::TIER_ENUMERATION);
+                            record_success(
+                                &mut outcomes,
+                                ev.id.clone(),
+                                Outcome::Success {
+                                    item_id: Some(item.id.clone()),
+                                    status: "Refreshed".to_string(),
+                                },
+                            );
                         }
                         Err(e) => {
                             error!("failed to refresh item: {}", e);
-                            succeeded.insert(ev.id.clone(), false);
+                            if let Some(queue) = &self.retry_queue {
+                                if let Err(queue_err) = queue
+                                    .enqueue(
+                                        &ev.id,
+                                        &ev.get_path(&self.rewrite),
+                                        Some(&item.id),
+                                        &e.to_string(),
+                                    )
+                                    .await
+                                {
+                                    error!("failed to enqueue retry for {}: {}", ev.id, queue_err);
+                                }
+                            }
+                            record_unless_success(
+                                &mut outcomes,
+                                ev.id.clone(),
+                                Outcome::Failure(e.to_string()),
+                            );
                         }
                     }
                 }
@@ -577,7 +1031,13 @@ impl TargetProcess for Emby {
                         "item not found after all methods: {}",
                         ev.get_path(&self.rewrite)
                     );
-                    succeeded.insert(ev.id.clone(), false);
+                    #[cfg(feature = "metrics")]
+                    scan_metrics::scan_skipped_missing(&self.url, &library.name, scan_metrics::TIER_ENUMERATION);
+                    record_unless_success(
+                        &mut outcomes,
+                        ev.id.clone(),
+                        Outcome::Fatal("not found after all methods".to_string()),
+                    );
                 }
             }
         } else if !remaining.is_empty() {
@@ -586,13 +1046,29 @@ impl TargetProcess for Emby {
                     "targeted scan failed for {} after all retries",
                     ev_path
                 );
-                succeeded.insert(ev.id.clone(), false);
+                if let Some(queue) = &self.retry_queue {
+                    if let Err(queue_err) = queue
+                        .enqueue(
+                            &ev.id,
+                            ev_path,
+                            None,
+                            "targeted scan failed after all retries",
+                        )
+                        .await
+                    {
+                        error!("failed to enqueue retry for {}: {}", ev.id, queue_err);
+                    }
+                }
+                outcomes.insert(
+                    ev.id.clone(),
+                    Outcome::Failure("targeted scan failed after all retries".to_string()),
+                );
             }
         }
 
-        Ok(succeeded
-            .iter()
-            .filter_map(|(k, v)| if *v { Some(k.clone()) } else { None })
-            .collect())
+        #[cfg(feature = "metrics")]
+        scan_metrics::process_duration(&self.url, process_start.elapsed());
+
+        Ok(outcomes.into_iter().collect())
     }
 }
@@ -0,0 +1,69 @@
+pub mod emby;
+
+use autopulse_database::models::ScanEvent;
+use serde::{Deserialize, Serialize};
+
+/// Per-event outcome of a [`TargetProcess::process`] call.
+///
+/// Collapsing every non-success case into a single bit makes it impossible for
+/// a caller to tell a retryable failure from a dead item, so this distinguishes
+/// the three cases a scheduler actually cares about. Lives here rather than on
+/// an individual target so every [`TargetProcess`] impl shares one vocabulary.
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// A scan/refresh was issued, or the path no longer exists or matches no
+    /// library, which is a benign skip rather than a failure.
+    Success {
+        /// Target-specific item id the event resolved to, if one was found.
+        item_id: Option<String>,
+        /// Human-readable status, e.g. `Created`, `Refreshed`, or `PathNotFound`.
+        status: String,
+    },
+    /// A transient problem occurred; the event is worth requeueing.
+    Failure(String),
+    /// The event could not be resolved by any method; requeueing it is unlikely to help.
+    Fatal(String),
+}
+
+/// Common interface implemented by every scan target (here [`emby::Emby`],
+/// which serves both Jellyfin and Emby) so the scheduler can process events
+/// without knowing which target it's talking to.
+pub trait TargetProcess {
+    /// Process `evs` against this target, returning the per-event [`Outcome`]
+    /// keyed by event id.
+    async fn process(&self, evs: &[&ScanEvent]) -> anyhow::Result<Vec<(String, Outcome)>>;
+}
+
+/// Shared HTTP request options for targets that talk to an HTTP API.
+#[derive(Serialize, Clone, Deserialize, Default)]
+pub struct Request {
+    /// Request timeout in seconds (default: no timeout)
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Request {
+    /// Build a [`reqwest::ClientBuilder`] seeded with `headers` and this
+    /// target's request options.
+    pub fn client_builder(&self, headers: reqwest::header::HeaderMap) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder().default_headers(headers);
+
+        if let Some(timeout_secs) = self.timeout_secs {
+            builder = builder.timeout(std::time::Duration::from_secs(timeout_secs));
+        }
+
+        builder
+    }
+}
+
+/// Extension trait that turns a [`reqwest::RequestBuilder`] into a response,
+/// treating 4xx and 5xx statuses as errors.
+pub trait RequestBuilderPerform {
+    async fn perform(self) -> anyhow::Result<reqwest::Response>;
+}
+
+impl RequestBuilderPerform for reqwest::RequestBuilder {
+    async fn perform(self) -> anyhow::Result<reqwest::Response> {
+        Ok(self.send().await?.error_for_status()?)
+    }
+}